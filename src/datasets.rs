@@ -9,6 +9,10 @@ use hugr::Hugr;
 use tket2::{json::load_tk1_json_str, portmatching::CircuitPattern};
 
 pub mod ecc;
+pub mod random;
+pub mod writer;
+mod manifest;
+mod record_store;
 
 pub trait Dataset {
     type Graph;
@@ -149,15 +153,11 @@ impl<T: CircuitDataset> Dataset for T {
     type Graph = Hugr;
 
     fn iter_qasm(&self) -> impl Iterator<Item = String> {
-        let qasm_bin_file = fs::File::open(self.circuit_folder().join("qasm.bin")).unwrap();
-        let qasm: Vec<String> = rmp_serde::decode::from_read(qasm_bin_file).unwrap();
-        qasm.into_iter()
+        record_store::RecordReader::open(&self.circuit_folder().join("qasm.bin")).unwrap()
     }
 
     fn iter_json(&self) -> impl Iterator<Item = String> {
-        let json_bin_file = fs::File::open(self.circuit_folder().join("json.bin")).unwrap();
-        let json: Vec<String> = rmp_serde::decode::from_read(json_bin_file).unwrap();
-        json.into_iter()
+        record_store::RecordReader::open(&self.circuit_folder().join("json.bin")).unwrap()
     }
 
     fn name(&self) -> String {
@@ -205,16 +205,25 @@ impl<T: CircuitDataset> Dataset for T {
                 file.save().expect("Failed to save file");
             }
         }
+        let qasm_store = folder.join("qasm.bin");
+        let json_store = folder.join("json.bin");
+
+        // Skip circuits that are already persisted so regeneration only
+        // appends circuits not yet in the stores.
+        let mut seen: HashSet<String> = record_store::read_all(&qasm_store)
+            .expect("Failed to read qasm store")
+            .into_iter()
+            .collect();
+
         let (qasm, json): (Vec<_>, Vec<_>) = files
             .into_iter()
             .filter(|f| f.valid_pattern())
             .map(|f| (f.qasm.contents.unwrap(), f.json.contents.unwrap()))
+            .filter(|(qasm, _)| seen.insert(qasm.clone()))
             .unzip();
 
-        let mut qasm_bin_file = fs::File::create(self.circuit_folder().join("qasm.bin")).unwrap();
-        let mut json_bin_file = fs::File::create(self.circuit_folder().join("json.bin")).unwrap();
-        rmp_serde::encode::write(&mut qasm_bin_file, &qasm).unwrap();
-        rmp_serde::encode::write(&mut json_bin_file, &json).unwrap();
+        record_store::append(&qasm_store, &qasm).expect("Failed to write qasm store");
+        record_store::append(&json_store, &json).expect("Failed to write json store");
         qasm.len()
     }
 }
@@ -1,22 +1,37 @@
 use std::{
     ffi::CString,
-    path::{Path, PathBuf}, fs,
+    fs,
+    path::{Path, PathBuf},
 };
 
+use serde_json::Value;
+
+use super::random::Gate;
+use super::writer::{CircuitWriter, Dialect};
 use super::FolderDataset;
 
 /// A circuit dataset obtained from an ECC file.
 pub struct ECCDataset {
     circuit_folder: PathBuf,
     ecc_file: PathBuf,
+    dialect: Dialect,
+    /// Parse the ECC file in pure Rust instead of going through the Quartz C
+    /// FFI, dropping the C toolchain dependency.
+    native: bool,
 }
 
 impl ECCDataset {
     /// Creates a new ECC dataset from an ECC file.
-    pub fn new(ecc_file: PathBuf, circuit_folder: PathBuf) -> Self {
+    ///
+    /// When `native` is set the ECC file is parsed in Rust and serialized
+    /// through the [`CircuitWriter`] path; otherwise the Quartz C bindings are
+    /// used.
+    pub fn new(ecc_file: PathBuf, circuit_folder: PathBuf, dialect: Dialect, native: bool) -> Self {
         Self {
             ecc_file,
             circuit_folder,
+            dialect,
+            native,
         }
     }
 }
@@ -24,7 +39,11 @@ impl ECCDataset {
 impl FolderDataset for ECCDataset {
     fn unpack(&self) {
         fs::create_dir_all(&self.circuit_folder).unwrap();
-        save_qasm(&self.ecc_file, &self.circuit_folder);
+        if self.native {
+            save_qasm_native(&self.ecc_file, &self.circuit_folder, &self.dialect);
+        } else {
+            save_qasm(&self.ecc_file, &self.circuit_folder);
+        }
     }
 
     fn circuit_folder(&self) -> &Path {
@@ -35,7 +54,7 @@ impl FolderDataset for ECCDataset {
 // This file is generated from the C header file found in the same directory.
 include!("../../quartz_bindings/bindings.rs");
 
-/// Converts an ECC file to QASM files.
+/// Converts an ECC file to QASM files through the Quartz C bindings.
 fn save_qasm(ecc_file: &Path, qasm_folder: &Path) {
     let ecc_file = CString::new(ecc_file.to_str().unwrap()).unwrap();
     let qasm_folder = CString::new(qasm_folder.to_str().unwrap()).unwrap();
@@ -43,3 +62,116 @@ fn save_qasm(ecc_file: &Path, qasm_folder: &Path) {
         ecc_to_qasm(ecc_file.as_ptr(), qasm_folder.as_ptr());
     }
 }
+
+/// Converts an ECC file to circuit files in pure Rust, writing one file per
+/// circuit with the dialect selected on the dataset.
+fn save_qasm_native(ecc_file: &Path, circuit_folder: &Path, writer: &dyn CircuitWriter) {
+    let contents = fs::read_to_string(ecc_file).expect("Failed to read ECC file");
+    let circuits = parse_ecc(&contents).expect("Failed to parse ECC file");
+    for (i, gates) in circuits.iter().enumerate() {
+        let n_qubits = gates
+            .iter()
+            .flat_map(Gate::qubits)
+            .max()
+            .map_or(0, |m| m + 1);
+        let mut circuit = writer.header(n_qubits);
+        for gate in gates {
+            circuit.push_str(&writer.emit_gate(gate));
+        }
+        let path = circuit_folder.join(format!("{}.{}", i, writer.extension()));
+        fs::write(path, circuit).unwrap();
+    }
+}
+
+/// Parses a Quartz ECC file into one [`Gate`] sequence per circuit.
+///
+/// The ECC file is a JSON document whose equivalence classes are lists of gate
+/// sequences; each gate is an array `[name, args...]` where the argument lists
+/// hold qubit (`Q<i>`) and parameter (`P<i>`) references. Symbolic rotation
+/// parameters have no concrete angle and are emitted as `0`.
+fn parse_ecc(contents: &str) -> serde_json::Result<Vec<Vec<Gate>>> {
+    let value: Value = serde_json::from_str(contents)?;
+    let mut circuits = Vec::new();
+    collect_circuits(&value, &mut circuits);
+    Ok(circuits)
+}
+
+/// Walks the ECC JSON, pushing every gate sequence it recognises.
+fn collect_circuits(value: &Value, out: &mut Vec<Vec<Gate>>) {
+    match value {
+        Value::Object(map) => map.values().for_each(|v| collect_circuits(v, out)),
+        Value::Array(items) if is_circuit(items) => {
+            out.push(items.iter().filter_map(parse_gate).collect())
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_circuits(v, out)),
+        _ => {}
+    }
+}
+
+/// A JSON array is a circuit when every element is a gate array, i.e. an array
+/// whose first element is the gate name.
+fn is_circuit(items: &[Value]) -> bool {
+    !items.is_empty()
+        && items.iter().all(|g| {
+            g.as_array()
+                .and_then(|a| a.first())
+                .is_some_and(Value::is_string)
+        })
+}
+
+/// Parses a single gate array `[name, args...]` into a [`Gate`].
+fn parse_gate(value: &Value) -> Option<Gate> {
+    let arr = value.as_array()?;
+    let name = arr.first()?.as_str()?.to_ascii_lowercase();
+
+    let mut qubits = Vec::new();
+    let mut params = Vec::new();
+    for entry in &arr[1..] {
+        let Some(list) = entry.as_array() else {
+            continue;
+        };
+        for item in list {
+            match item {
+                Value::String(s) => match s.chars().next() {
+                    Some('P' | 'p') => params.push(0.0),
+                    Some('Q' | 'q') => {
+                        if let Ok(idx) = s[1..].parse() {
+                            qubits.push(idx);
+                        }
+                    }
+                    _ => {
+                        if let Ok(idx) = s.parse() {
+                            qubits.push(idx);
+                        }
+                    }
+                },
+                Value::Number(n) if n.is_u64() => qubits.push(n.as_u64().unwrap() as usize),
+                Value::Number(n) => params.push(n.as_f64().unwrap()),
+                _ => {}
+            }
+        }
+    }
+
+    gate_from_parts(&name, &qubits, &params)
+}
+
+/// Builds a [`Gate`] from its parsed name, qubits and parameters.
+fn gate_from_parts(name: &str, qubits: &[usize], params: &[f64]) -> Option<Gate> {
+    let q = |i: usize| qubits.get(i).copied();
+    let theta = params.first().copied().unwrap_or(0.0);
+    Some(match name {
+        "cx" | "cnot" => Gate::Cx(q(0)?, q(1)?),
+        "h" => Gate::H(q(0)?),
+        "t" => Gate::T(q(0)?),
+        "tdg" | "tdag" => Gate::Tdg(q(0)?),
+        "s" => Gate::S(q(0)?),
+        "sdg" | "sdag" => Gate::Sdg(q(0)?),
+        "x" => Gate::X(q(0)?),
+        "y" => Gate::Y(q(0)?),
+        "z" => Gate::Z(q(0)?),
+        "rz" => Gate::Rz(q(0)?, theta),
+        "rx" => Gate::Rx(q(0)?, theta),
+        "ry" => Gate::Ry(q(0)?, theta),
+        _ => return None,
+    })
+}
@@ -0,0 +1,108 @@
+//! Reproducibility manifest for generated datasets.
+//!
+//! Random datasets name their circuit files by the SHA-256 of the circuit
+//! text, so regenerating from the same seed yields the same files. A
+//! `manifest.json` written next to them records exactly how the dataset was
+//! produced (sizes, seed, gate set, dialect) plus the hashes of every circuit,
+//! which [`verify`] re-checks against the files on disk.
+
+use std::{collections::HashSet, fs, io, path::Path};
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// The per-gate sampling weight as recorded in the manifest.
+pub(crate) struct GateWeight {
+    pub gate: String,
+    pub weight: f64,
+}
+
+/// A record of how a dataset was generated.
+pub(crate) struct Manifest {
+    pub n_circuits: usize,
+    pub n_qubits: usize,
+    pub n_gates: usize,
+    pub seed: u64,
+    /// Number of seed streams generation was partitioned into; regenerating
+    /// with a different value would produce a different set.
+    pub n_workers: usize,
+    pub gate_set: Vec<GateWeight>,
+    pub angles: Option<Vec<f64>>,
+    pub dialect: String,
+    pub topology: String,
+    pub circuit_hashes: Vec<String>,
+}
+
+/// The SHA-256 of a circuit's text, as lower-case hex.
+pub(crate) fn content_hash(contents: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(contents.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+impl Manifest {
+    /// The manifest as a JSON value.
+    fn to_json(&self) -> Value {
+        let gate_set: Vec<Value> = self
+            .gate_set
+            .iter()
+            .map(|g| json!({ "gate": g.gate, "weight": g.weight }))
+            .collect();
+        json!({
+            "n_circuits": self.n_circuits,
+            "n_qubits": self.n_qubits,
+            "n_gates": self.n_gates,
+            "seed": self.seed,
+            "n_workers": self.n_workers,
+            "gate_set": gate_set,
+            "angles": self.angles,
+            "dialect": self.dialect,
+            "topology": self.topology,
+            "circuit_hashes": self.circuit_hashes,
+        })
+    }
+
+    /// Writes the manifest as `manifest.json` into `folder`.
+    pub(crate) fn write(&self, folder: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_json())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        fs::write(folder.join("manifest.json"), json)
+    }
+}
+
+/// Re-reads every circuit file in `folder` and checks its content hash against
+/// the committed manifest, returning `true` when they agree exactly.
+///
+/// `n_workers` is the seed-stream count this build generates with; if it
+/// differs from the value recorded in the manifest, regeneration here would
+/// diverge from the committed set, so the check fails.
+pub(crate) fn verify(folder: &Path, extension: &str, n_workers: usize) -> io::Result<bool> {
+    let json = fs::read_to_string(folder.join("manifest.json"))?;
+    let manifest: Value =
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if manifest["n_workers"].as_u64() != Some(n_workers as u64) {
+        return Ok(false);
+    }
+    let expected: HashSet<&str> = manifest["circuit_hashes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+
+    let mut found = HashSet::new();
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(extension) {
+            continue;
+        }
+        let hash = content_hash(&fs::read_to_string(&path)?);
+        // The file name is the content hash; a rename or edit breaks it.
+        if path.file_stem().and_then(|s| s.to_str()) != Some(hash.as_str()) {
+            return Ok(false);
+        }
+        found.insert(hash);
+    }
+
+    Ok(found.len() == expected.len() && found.iter().all(|h| expected.contains(h.as_str())))
+}
@@ -1,54 +1,106 @@
 use std::{
-    cell::RefCell,
-    collections::HashSet,
-    fs,
+    collections::BTreeMap,
+    f64::consts::TAU,
+    fs, io,
     path::{Path, PathBuf},
 };
 
 use itertools::Itertools;
-use rand::Rng;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use rayon::prelude::*;
 use union_find::{QuickUnionUf, UnionBySize, UnionFind};
-use uuid::Uuid;
 
+use super::manifest::{self, GateWeight, Manifest};
+use super::writer::{CircuitWriter, Dialect};
 use super::FolderDataset;
 
+/// Number of independent seed streams circuit generation is partitioned into.
+///
+/// Fixed, rather than `rayon::current_num_threads()`, so the produced dataset
+/// is a pure function of the seed alone and reproduces on any host regardless
+/// of its core count; rayon still schedules the streams across all cores.
+const N_SEED_STREAMS: usize = 16;
+
 /// Dataset of random circuits.
-pub struct RandomDataset<R> {
+pub struct RandomDataset {
     circuit_folder: PathBuf,
     n_circuits: usize,
     n_qubits: usize,
     n_gates: usize,
-    rng: RefCell<R>,
+    gate_set: GateSet,
+    dialect: Dialect,
+    topology: Topology,
+    seed: u64,
 }
 
-impl<R: Rng> FolderDataset for RandomDataset<R> {
+impl FolderDataset for RandomDataset {
     fn unpack(&self) {
+        // The `generate()` pipeline only reads `.qasm`/`.json` files and
+        // converts through the OpenQASM parser, so a dialect that writes any
+        // other extension (e.g. cQASM's `.cq`) would be silently dropped.
+        // Reject it up front rather than emitting an empty record store.
+        assert_eq!(
+            self.dialect.extension(),
+            "qasm",
+            "RandomDataset feeds the QASM-only generate() pipeline, but dialect \
+             {} writes .{} files",
+            self.dialect.name(),
+            self.dialect.extension(),
+        );
+
         fs::create_dir_all(&self.circuit_folder).unwrap();
 
-        let mut seen_circs = HashSet::new();
-        let mut n_circs = 0;
-        let mut n_iter = 0;
-        loop {
-            // Generate random circuit, save if not seen before
-            let Some(qasm) =
-                random_circuit(self.n_qubits, self.n_gates, &mut *self.rng.borrow_mut())
-            else {
-                continue;
-            };
-            if seen_circs.insert(qasm.clone()) {
-                let path = self.circuit_folder.join(format!("{}.qasm", Uuid::new_v4()));
-                fs::write(path, qasm).unwrap();
-                n_circs += 1;
-                if n_circs == self.n_circuits {
-                    break;
-                }
-            }
-            // Make sure we are not in an infinite loop (if params are too small)
-            n_iter += 1;
-            if n_iter > 10 * self.n_circuits {
-                panic!("Could not generate {} circuits", self.n_circuits);
-            }
+        // Each stream explores a disjoint, deterministic seed sequence derived
+        // from the base seed and draws a fixed number of candidates — counts
+        // that depend on neither a shared stop condition nor the host's core
+        // count. The produced set is therefore a pure function of the seed,
+        // independent of how rayon interleaves the streams.
+        let attempts_per_stream = (10 * self.n_circuits).div_ceil(N_SEED_STREAMS).max(1);
+        let candidates: Vec<Vec<String>> = (0..N_SEED_STREAMS)
+            .into_par_iter()
+            .map(|stream| {
+                let mut rng = SmallRng::seed_from_u64(self.seed.wrapping_add(stream as u64));
+                (0..attempts_per_stream)
+                    .filter_map(|_| {
+                        random_circuit(
+                            self.n_qubits,
+                            self.n_gates,
+                            &self.gate_set,
+                            &self.topology,
+                            &self.dialect,
+                            &mut rng,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Deduplicate and order canonically by content hash, then keep the
+        // first `n_circuits`. A `BTreeMap` keyed by the hash gives both the
+        // dedup and a scheduling-independent order in one pass, so the content-
+        // addressed filenames and committed manifest pin an exact dataset.
+        let mut by_hash: BTreeMap<String, String> = BTreeMap::new();
+        for qasm in candidates.into_iter().flatten() {
+            by_hash
+                .entry(manifest::content_hash(&qasm))
+                .or_insert(qasm);
+        }
+        if by_hash.len() < self.n_circuits {
+            panic!("Could not generate {} circuits", self.n_circuits);
+        }
+
+        let mut hashes = Vec::with_capacity(self.n_circuits);
+        for (hash, qasm) in by_hash.into_iter().take(self.n_circuits) {
+            let path = self
+                .circuit_folder
+                .join(format!("{}.{}", hash, self.dialect.extension()));
+            fs::write(path, qasm).unwrap();
+            hashes.push(hash);
         }
+
+        self.manifest(hashes)
+            .write(&self.circuit_folder)
+            .expect("Failed to write manifest");
     }
 
     fn circuit_folder(&self) -> &Path {
@@ -56,75 +108,353 @@ impl<R: Rng> FolderDataset for RandomDataset<R> {
     }
 }
 
-impl<R> RandomDataset<R> {
+impl RandomDataset {
     /// Creates a new random dataset.
     pub fn new(
-        rng: R,
         n_circuits: usize,
         n_qubits: usize,
         n_gates: usize,
+        gate_set: GateSet,
+        dialect: Dialect,
+        topology: Topology,
+        seed: u64,
         circuit_folder: PathBuf,
     ) -> Self {
         Self {
-            rng: RefCell::new(rng),
             circuit_folder,
             n_circuits,
             n_qubits,
             n_gates,
+            gate_set,
+            dialect,
+            topology,
+            seed,
+        }
+    }
+
+    /// Checks the circuit files on disk against the committed `manifest.json`,
+    /// returning `true` when every file's content hash matches.
+    pub fn verify(&self) -> std::io::Result<bool> {
+        manifest::verify(&self.circuit_folder, self.dialect.extension(), N_SEED_STREAMS)
+    }
+
+    /// Builds the reproducibility manifest for the given circuit hashes.
+    fn manifest(&self, circuit_hashes: Vec<String>) -> Manifest {
+        Manifest {
+            n_circuits: self.n_circuits,
+            n_qubits: self.n_qubits,
+            n_gates: self.n_gates,
+            seed: self.seed,
+            n_workers: N_SEED_STREAMS,
+            gate_set: self
+                .gate_set
+                .weights()
+                .iter()
+                .map(|(kind, weight)| GateWeight {
+                    gate: kind.name().to_string(),
+                    weight: *weight,
+                })
+                .collect(),
+            angles: self.gate_set.angles().map(<[f64]>::to_vec),
+            dialect: self.dialect.name().to_string(),
+            topology: self.topology.name().to_string(),
+            circuit_hashes,
         }
     }
 }
 
-fn random_circuit(n_qubits: usize, n_gates: usize, rng: &mut impl Rng) -> Option<String> {
+fn random_circuit(
+    n_qubits: usize,
+    n_gates: usize,
+    gate_set: &GateSet,
+    topology: &Topology,
+    writer: &dyn CircuitWriter,
+    rng: &mut impl Rng,
+) -> Option<String> {
     assert!(n_qubits <= n_gates + 1);
-    let gates = (0..n_gates).map(|_| Gate::random(n_qubits, rng));
-    let mut uf = QuickUnionUf::<UnionBySize>::new(n_qubits);
-
-    let mut qasm = format!(
-        r#"
-OPENQASM 2.0;
-include "qelib1.inc";
-qreg q[{}];"#,
-        n_qubits
+    // Restrict two-qubit gates to the hardware coupling map.
+    let edges = topology.edges(n_qubits);
+    // With no coupling edges, `Cx` can never be placed; if the gate set offers
+    // nothing else, `Gate::random` would loop forever, so reject up front.
+    let only_cx = gate_set
+        .weights()
+        .iter()
+        .all(|(kind, _)| matches!(kind, GateKind::Cx));
+    assert!(
+        !edges.is_empty() || !only_cx,
+        "unsatisfiable generator: the gate set can only emit Cx but the \
+         topology provides no coupling edges for {n_qubits} qubit(s)"
     );
+    let gates = (0..n_gates).map(|_| Gate::random(n_qubits, gate_set, &edges, rng));
+    let mut uf = QuickUnionUf::<UnionBySize>::new(n_qubits);
 
+    let mut circuit = writer.header(n_qubits);
     for g in gates {
-        match g {
-            Gate::Cx(a, b) => {
-                uf.union(a, b);
-                qasm.push_str(&format!("cx q[{}],q[{}];\n", a, b));
-            }
-            Gate::H(a) => qasm.push_str(&format!("h q[{}];\n", a)),
-            Gate::T(a) => qasm.push_str(&format!("t q[{}];\n", a)),
-            // Gate::Tdg(a) => qasm.push_str(&format!("tdg q[{}];\n", a)),
-            Gate::Invalid => unreachable!(),
+        // Track two-qubit connectivity independently of the chosen dialect.
+        if let Gate::Cx(a, b) = g {
+            uf.union(a, b);
         }
+        circuit.push_str(&writer.emit_gate(&g));
     }
     // check that all qubits are connected
     (0..n_qubits)
         .map(|i| uf.find(i))
         .all_equal()
-        .then_some(qasm)
+        .then_some(circuit)
+}
+
+/// The gates that may appear in a random circuit, together with their sampling
+/// weights and the angle distribution used for parametric rotations.
+pub struct GateSet {
+    gates: Vec<(GateKind, f64)>,
+    /// Angles drawn for `Rz`/`Rx`/`Ry`; `None` samples uniformly from `[0, 2π)`.
+    angles: Option<Vec<f64>>,
 }
 
-enum Gate {
+impl Default for GateSet {
+    /// The historical Clifford+T skeleton: `Cx`, `H` and `T` with equal weight.
+    fn default() -> Self {
+        Self {
+            gates: vec![
+                (GateKind::Cx, 1.0),
+                (GateKind::H, 1.0),
+                (GateKind::T, 1.0),
+            ],
+            angles: None,
+        }
+    }
+}
+
+impl GateSet {
+    /// Creates a gate set from per-gate sampling weights.
+    pub fn new(gates: Vec<(GateKind, f64)>) -> Self {
+        Self {
+            gates,
+            angles: None,
+        }
+    }
+
+    /// Restricts parametric rotations to draw their angle from `angles`,
+    /// instead of uniformly from `[0, 2π)`.
+    pub fn with_angles(mut self, angles: Vec<f64>) -> Self {
+        self.angles = Some(angles);
+        self
+    }
+
+    /// Draws a rotation angle according to the configured distribution.
+    fn sample_angle(&self, rng: &mut impl Rng) -> f64 {
+        match &self.angles {
+            Some(angles) => angles[rng.gen_range(0..angles.len())],
+            None => rng.gen_range(0.0..TAU),
+        }
+    }
+
+    /// The configured per-gate sampling weights.
+    pub fn weights(&self) -> &[(GateKind, f64)] {
+        &self.gates
+    }
+
+    /// The user-supplied rotation angles, if any.
+    pub fn angles(&self) -> Option<&[f64]> {
+        self.angles.as_deref()
+    }
+
+    /// Picks a gate kind according to the configured weights.
+    fn sample_kind(&self, rng: &mut impl Rng) -> GateKind {
+        let total: f64 = self.gates.iter().map(|(_, w)| w).sum();
+        let mut pick = rng.gen_range(0.0..total);
+        for &(kind, weight) in &self.gates {
+            if pick < weight {
+                return kind;
+            }
+            pick -= weight;
+        }
+        // Only reachable through floating point rounding on the last bucket.
+        self.gates.last().unwrap().0
+    }
+}
+
+/// A gate kind, independent of the qubits or angle it is later applied to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GateKind {
+    Cx,
+    H,
+    T,
+    Tdg,
+    S,
+    Sdg,
+    X,
+    Y,
+    Z,
+    Rz,
+    Rx,
+    Ry,
+}
+
+impl GateKind {
+    /// The QASM name of the gate, recorded in dataset manifests.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GateKind::Cx => "cx",
+            GateKind::H => "h",
+            GateKind::T => "t",
+            GateKind::Tdg => "tdg",
+            GateKind::S => "s",
+            GateKind::Sdg => "sdg",
+            GateKind::X => "x",
+            GateKind::Y => "y",
+            GateKind::Z => "z",
+            GateKind::Rz => "rz",
+            GateKind::Rx => "rx",
+            GateKind::Ry => "ry",
+        }
+    }
+}
+
+/// The qubit coupling map that two-qubit gates are constrained to.
+///
+/// Presets are generated to fit the circuit's qubit count; [`Topology::Custom`]
+/// carries an explicit adjacency list, e.g. loaded with [`Topology::from_file`].
+pub enum Topology {
+    /// Every pair of qubits may interact (the historical default).
+    AllToAll,
+    /// A linear nearest-neighbour chain `0-1-2-…`.
+    Linear,
+    /// A near-square 2D grid with nearest-neighbour couplings.
+    Grid,
+    /// A heavy-hexagon–style lattice: a grid whose vertical links are thinned
+    /// to alternating columns, capping qubit degree at three as on IBM devices.
+    HeavyHex,
+    /// An arbitrary coupling map given as an explicit edge list.
+    Custom(Vec<(usize, usize)>),
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Topology::AllToAll
+    }
+}
+
+impl Topology {
+    /// Reads a custom coupling map from `path`, one `a b` edge per line.
+    ///
+    /// Blank lines and `#` comments are ignored.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut edges = Vec::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let parse = |p: Option<&str>| {
+                p.and_then(|s| s.parse::<usize>().ok())
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed edge"))
+            };
+            let a = parse(parts.next())?;
+            let b = parse(parts.next())?;
+            edges.push((a, b));
+        }
+        Ok(Topology::Custom(edges))
+    }
+
+    /// The undirected coupling edges for an `n_qubits`-qubit circuit.
+    fn edges(&self, n_qubits: usize) -> Vec<(usize, usize)> {
+        match self {
+            Topology::AllToAll => (0..n_qubits).tuple_combinations().collect(),
+            Topology::Linear => (0..n_qubits.saturating_sub(1)).map(|i| (i, i + 1)).collect(),
+            Topology::Grid | Topology::HeavyHex => {
+                // Lay the qubits on a near-square grid.
+                let cols = (n_qubits as f64).sqrt().ceil() as usize;
+                let cols = cols.max(1);
+                let mut edges = Vec::new();
+                for q in 0..n_qubits {
+                    let (row, col) = (q / cols, q % cols);
+                    // Horizontal neighbour.
+                    if col + 1 < cols && q + 1 < n_qubits {
+                        edges.push((q, q + 1));
+                    }
+                    // Vertical neighbour: every column on a grid, alternating
+                    // columns on heavy-hex to thin the lattice.
+                    let keep_vertical =
+                        matches!(self, Topology::Grid) || (row + col) % 2 == 0;
+                    if keep_vertical && q + cols < n_qubits {
+                        edges.push((q, q + cols));
+                    }
+                }
+                edges
+            }
+            Topology::Custom(edges) => edges
+                .iter()
+                .copied()
+                .filter(|&(a, b)| a < n_qubits && b < n_qubits)
+                .collect(),
+        }
+    }
+
+    /// A stable name for the topology, recorded in dataset manifests.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::AllToAll => "all-to-all",
+            Topology::Linear => "linear",
+            Topology::Grid => "grid",
+            Topology::HeavyHex => "heavy-hex",
+            Topology::Custom(_) => "custom",
+        }
+    }
+}
+
+pub(crate) enum Gate {
     Cx(usize, usize),
     H(usize),
     T(usize),
-    // Tdg(usize),
+    Tdg(usize),
+    S(usize),
+    Sdg(usize),
+    X(usize),
+    Y(usize),
+    Z(usize),
+    Rz(usize, f64),
+    Rx(usize, f64),
+    Ry(usize, f64),
     Invalid,
 }
 
 impl Gate {
-    fn random(n_qubits: usize, rng: &mut impl Rng) -> Self {
+    fn random(
+        n_qubits: usize,
+        gate_set: &GateSet,
+        edges: &[(usize, usize)],
+        rng: &mut impl Rng,
+    ) -> Self {
         assert!(n_qubits > 0);
         let mut g = Self::Invalid;
         while !g.is_valid() {
-            g = match rng.gen_range(0..3) {
-                0 => Gate::Cx(rng.gen_range(0..n_qubits), rng.gen_range(0..n_qubits)),
-                1 => Gate::H(rng.gen_range(0..n_qubits)),
-                _ => Gate::T(rng.gen_range(0..n_qubits)),
-                // _ => Gate::Tdg(rng.gen_range(0..n_qubits)),
+            g = match gate_set.sample_kind(rng) {
+                // Place `Cx` only on a coupling-map edge; an orientation is
+                // drawn uniformly. With no edges available, fall through as
+                // invalid so a different gate is resampled.
+                GateKind::Cx if edges.is_empty() => Gate::Invalid,
+                GateKind::Cx => {
+                    let (a, b) = edges[rng.gen_range(0..edges.len())];
+                    if rng.gen() {
+                        Gate::Cx(a, b)
+                    } else {
+                        Gate::Cx(b, a)
+                    }
+                }
+                GateKind::H => Gate::H(rng.gen_range(0..n_qubits)),
+                GateKind::T => Gate::T(rng.gen_range(0..n_qubits)),
+                GateKind::Tdg => Gate::Tdg(rng.gen_range(0..n_qubits)),
+                GateKind::S => Gate::S(rng.gen_range(0..n_qubits)),
+                GateKind::Sdg => Gate::Sdg(rng.gen_range(0..n_qubits)),
+                GateKind::X => Gate::X(rng.gen_range(0..n_qubits)),
+                GateKind::Y => Gate::Y(rng.gen_range(0..n_qubits)),
+                GateKind::Z => Gate::Z(rng.gen_range(0..n_qubits)),
+                GateKind::Rz => Gate::Rz(rng.gen_range(0..n_qubits), gate_set.sample_angle(rng)),
+                GateKind::Rx => Gate::Rx(rng.gen_range(0..n_qubits), gate_set.sample_angle(rng)),
+                GateKind::Ry => Gate::Ry(rng.gen_range(0..n_qubits), gate_set.sample_angle(rng)),
             };
         }
         g
@@ -137,4 +467,23 @@ impl Gate {
             _ => true,
         }
     }
+
+    /// The qubits the gate acts on, in argument order.
+    pub(crate) fn qubits(&self) -> Vec<usize> {
+        match *self {
+            Gate::Cx(a, b) => vec![a, b],
+            Gate::H(a)
+            | Gate::T(a)
+            | Gate::Tdg(a)
+            | Gate::S(a)
+            | Gate::Sdg(a)
+            | Gate::X(a)
+            | Gate::Y(a)
+            | Gate::Z(a)
+            | Gate::Rz(a, _)
+            | Gate::Rx(a, _)
+            | Gate::Ry(a, _) => vec![a],
+            Gate::Invalid => vec![],
+        }
+    }
 }
@@ -0,0 +1,145 @@
+//! A streaming, appendable on-disk store for the per-circuit QASM/JSON
+//! strings.
+//!
+//! The datasets used to be persisted as a single msgpack `Vec<String>`, which
+//! `iter_*` deserialized into memory in full before iteration and which
+//! `generate()` rewrote from scratch on every run. Here each circuit is framed
+//! independently — a `u64` record count followed by varint-length-prefixed
+//! msgpack strings — so reads decode one circuit at a time and writes can
+//! append to an existing store, keeping peak memory bounded by a single
+//! circuit and making regeneration incremental.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+/// Number of bytes in the record-count header.
+const HEADER_LEN: u64 = 8;
+
+/// Writes a varint (LEB128) encoded `u64`.
+fn write_varint(w: &mut impl Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value != 0 {
+            byte |= 0x80;
+        }
+        w.write_all(&[byte])?;
+        if value == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Reads a varint (LEB128) encoded `u64`.
+fn read_varint(r: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        r.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+/// Appends `records` to the store at `path`, creating it if absent.
+///
+/// The count header is updated in place so the store stays a valid sequence of
+/// length-prefixed records after every append.
+pub(crate) fn append<I, S>(path: &Path, records: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(path)?;
+
+    let mut count = if file.metadata()?.len() >= HEADER_LEN {
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header)?;
+        u64::from_le_bytes(header)
+    } else {
+        file.write_all(&0u64.to_le_bytes())?;
+        0
+    };
+
+    file.seek(SeekFrom::End(0))?;
+    let mut writer = BufWriter::new(&mut file);
+    let mut written = 0u64;
+    for record in records {
+        let payload = rmp_serde::to_vec(record.as_ref())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        write_varint(&mut writer, payload.len() as u64)?;
+        writer.write_all(&payload)?;
+        written += 1;
+    }
+    writer.flush()?;
+    drop(writer);
+
+    count += written;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&count.to_le_bytes())?;
+    Ok(())
+}
+
+/// A lazy iterator over the records of a store, decoding one at a time.
+pub(crate) struct RecordReader {
+    reader: BufReader<File>,
+    remaining: u64,
+}
+
+impl RecordReader {
+    /// Opens the store at `path` for streaming reads.
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        Ok(Self {
+            reader,
+            remaining: u64::from_le_bytes(header),
+        })
+    }
+}
+
+impl Iterator for RecordReader {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let len = read_varint(&mut self.reader).expect("truncated record store");
+        let mut payload = vec![0u8; len as usize];
+        self.reader
+            .read_exact(&mut payload)
+            .expect("truncated record store");
+        Some(rmp_serde::from_slice(&payload).expect("corrupt record store"))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.remaining as usize;
+        (n, Some(n))
+    }
+}
+
+/// Reads every record of a store into a vector, or an empty vector if it is
+/// absent.
+///
+/// Used by `generate()` to skip circuits that are already persisted.
+pub(crate) fn read_all(path: &Path) -> io::Result<Vec<String>> {
+    match RecordReader::open(path) {
+        Ok(reader) => Ok(reader.collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
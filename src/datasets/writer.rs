@@ -0,0 +1,161 @@
+//! Output-format abstraction for circuit serialization.
+//!
+//! A [`CircuitWriter`] turns the internal [`Gate`] model into a textual circuit
+//! in a particular dialect, controlling both the file header and the per-gate
+//! rendering. This lets the datasets emit OpenQASM 2.0, OpenQASM 3.0 or cQASM
+//! without locking circuit generation to one format, mirroring how simulators
+//! expose several exporters side by side.
+
+use super::random::Gate;
+
+/// Serializes circuits in a particular textual dialect.
+pub trait CircuitWriter {
+    /// The file preamble emitted once before any gate, declaring the qubit
+    /// register of size `n_qubits`.
+    fn header(&self, n_qubits: usize) -> String;
+
+    /// Renders a single gate application, including its trailing separator.
+    fn emit_gate(&self, gate: &Gate) -> String;
+
+    /// The file extension used when circuits are written to separate files.
+    fn extension(&self) -> &'static str;
+}
+
+/// The dialect a dataset serializes its circuits in.
+#[derive(Clone, Copy, Default)]
+pub enum Dialect {
+    #[default]
+    OpenQasm2,
+    OpenQasm3,
+    CQasm,
+}
+
+impl CircuitWriter for Dialect {
+    fn header(&self, n_qubits: usize) -> String {
+        match self {
+            Dialect::OpenQasm2 => OpenQasm2.header(n_qubits),
+            Dialect::OpenQasm3 => OpenQasm3.header(n_qubits),
+            Dialect::CQasm => CQasm.header(n_qubits),
+        }
+    }
+
+    fn emit_gate(&self, gate: &Gate) -> String {
+        match self {
+            Dialect::OpenQasm2 => OpenQasm2.emit_gate(gate),
+            Dialect::OpenQasm3 => OpenQasm3.emit_gate(gate),
+            Dialect::CQasm => CQasm.emit_gate(gate),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Dialect::OpenQasm2 => OpenQasm2.extension(),
+            Dialect::OpenQasm3 => OpenQasm3.extension(),
+            Dialect::CQasm => CQasm.extension(),
+        }
+    }
+}
+
+impl Dialect {
+    /// A stable name for the dialect, recorded in dataset manifests.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dialect::OpenQasm2 => "openqasm2",
+            Dialect::OpenQasm3 => "openqasm3",
+            Dialect::CQasm => "cqasm",
+        }
+    }
+}
+
+/// OpenQASM 2.0, the historical default.
+pub struct OpenQasm2;
+
+impl CircuitWriter for OpenQasm2 {
+    fn header(&self, n_qubits: usize) -> String {
+        format!(
+            r#"
+OPENQASM 2.0;
+include "qelib1.inc";
+qreg q[{}];"#,
+            n_qubits
+        )
+    }
+
+    fn emit_gate(&self, gate: &Gate) -> String {
+        match gate {
+            Gate::Cx(a, b) => format!("cx q[{}],q[{}];\n", a, b),
+            Gate::H(a) => format!("h q[{}];\n", a),
+            Gate::T(a) => format!("t q[{}];\n", a),
+            Gate::Tdg(a) => format!("tdg q[{}];\n", a),
+            Gate::S(a) => format!("s q[{}];\n", a),
+            Gate::Sdg(a) => format!("sdg q[{}];\n", a),
+            Gate::X(a) => format!("x q[{}];\n", a),
+            Gate::Y(a) => format!("y q[{}];\n", a),
+            Gate::Z(a) => format!("z q[{}];\n", a),
+            Gate::Rz(a, theta) => format!("rz({}) q[{}];\n", theta, a),
+            Gate::Rx(a, theta) => format!("rx({}) q[{}];\n", theta, a),
+            Gate::Ry(a, theta) => format!("ry({}) q[{}];\n", theta, a),
+            Gate::Invalid => unreachable!(),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        "qasm"
+    }
+}
+
+/// OpenQASM 3.0, using the `stdgates.inc` standard library.
+pub struct OpenQasm3;
+
+impl CircuitWriter for OpenQasm3 {
+    fn header(&self, n_qubits: usize) -> String {
+        format!(
+            r#"
+OPENQASM 3.0;
+include "stdgates.inc";
+qubit[{}] q;
+"#,
+            n_qubits
+        )
+    }
+
+    fn emit_gate(&self, gate: &Gate) -> String {
+        // The gate names coincide with OpenQASM 2.0; only the header differs.
+        OpenQasm2.emit_gate(gate)
+    }
+
+    fn extension(&self) -> &'static str {
+        "qasm"
+    }
+}
+
+/// cQASM 1.0, as consumed by the QX family of simulators.
+pub struct CQasm;
+
+impl CircuitWriter for CQasm {
+    fn header(&self, n_qubits: usize) -> String {
+        format!("version 1.0\nqubits {}\n", n_qubits)
+    }
+
+    fn emit_gate(&self, gate: &Gate) -> String {
+        match gate {
+            Gate::Cx(a, b) => format!("cnot q[{}], q[{}]\n", a, b),
+            Gate::H(a) => format!("h q[{}]\n", a),
+            Gate::T(a) => format!("t q[{}]\n", a),
+            Gate::Tdg(a) => format!("tdag q[{}]\n", a),
+            Gate::S(a) => format!("s q[{}]\n", a),
+            Gate::Sdg(a) => format!("sdag q[{}]\n", a),
+            Gate::X(a) => format!("x q[{}]\n", a),
+            Gate::Y(a) => format!("y q[{}]\n", a),
+            Gate::Z(a) => format!("z q[{}]\n", a),
+            Gate::Rz(a, theta) => format!("rz q[{}], {}\n", a, theta),
+            Gate::Rx(a, theta) => format!("rx q[{}], {}\n", a, theta),
+            Gate::Ry(a, theta) => format!("ry q[{}], {}\n", a, theta),
+            Gate::Invalid => unreachable!(),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        "cq"
+    }
+}
@@ -0,0 +1,55 @@
+//! Helpers for moving circuits between the QASM and tket1 JSON
+//! representations the benchmark consumes.
+
+mod native;
+#[cfg(feature = "python-fallback")]
+mod qasm_conversion;
+
+use std::io;
+
+use native::NativeConvertError;
+
+/// Converts a QASM string to a tket1 JSON string.
+///
+/// Uses the native parser (see [`native`]); when the `python-fallback`
+/// feature is enabled and the native path fails (e.g. on a gate it does not
+/// recognise), the conversion is retried through the
+/// `py-scripts/single_qasm_to_json.py` helper.
+pub(crate) fn qasm_to_json(qasm: &str) -> io::Result<String> {
+    match native::qasm_to_json(qasm) {
+        Ok(json) => Ok(json),
+        Err(err) => fallback_qasm_to_json(qasm, err),
+    }
+}
+
+/// Converts a tket1 JSON string to a QASM string.
+///
+/// Uses the native emitter (see [`native`]), falling back to the Python
+/// helper under the `python-fallback` feature when the native path fails
+/// (e.g. on a command it does not recognise).
+pub(crate) fn json_to_qasm(json: &str) -> io::Result<String> {
+    match native::json_to_qasm(json) {
+        Ok(qasm) => Ok(qasm),
+        Err(err) => fallback_json_to_qasm(json, err),
+    }
+}
+
+#[cfg(feature = "python-fallback")]
+fn fallback_qasm_to_json(qasm: &str, _err: NativeConvertError) -> io::Result<String> {
+    qasm_conversion::qasm_to_json(qasm)
+}
+
+#[cfg(not(feature = "python-fallback"))]
+fn fallback_qasm_to_json(_qasm: &str, err: NativeConvertError) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(feature = "python-fallback")]
+fn fallback_json_to_qasm(json: &str, _err: NativeConvertError) -> io::Result<String> {
+    qasm_conversion::json_to_qasm(json)
+}
+
+#[cfg(not(feature = "python-fallback"))]
+fn fallback_json_to_qasm(_json: &str, err: NativeConvertError) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::InvalidData, err))
+}
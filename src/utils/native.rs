@@ -0,0 +1,417 @@
+//! Native OpenQASM 2.0 ↔ tket1 JSON conversion.
+//!
+//! The benchmark used to shell out to `python` once per circuit, which
+//! dominated `generate()` for the large random datasets. This module parses
+//! OpenQASM 2.0 directly and emits the same dict `load_tk1_json_str` consumes,
+//! round-tripping back to QASM for the reverse direction, so no subprocess is
+//! spawned on the hot path.
+
+use std::{collections::BTreeMap, error::Error, f64::consts::PI, fmt};
+
+use serde_json::{json, Value};
+
+/// An error raised when the native converter does not recognise part of its
+/// input. When the `python-fallback` feature is enabled the caller retries the
+/// conversion through the Python helper scripts.
+#[derive(Debug)]
+pub(crate) struct NativeConvertError(String);
+
+impl fmt::Display for NativeConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "native conversion failed: {}", self.0)
+    }
+}
+
+impl Error for NativeConvertError {}
+
+impl NativeConvertError {
+    fn new(msg: impl Into<String>) -> Self {
+        Self(msg.into())
+    }
+}
+
+type Result<T> = std::result::Result<T, NativeConvertError>;
+
+/// A single gate application in register-indexed form.
+#[derive(Debug, Clone)]
+pub(crate) struct Command {
+    /// Lower-case QASM gate name, e.g. `cx`, `rz`.
+    name: String,
+    /// Quantum register arguments as `(register, index)` pairs.
+    qubits: Vec<(String, usize)>,
+    /// Classical register arguments as `(register, index)` pairs.
+    clbits: Vec<(String, usize)>,
+    /// Parameters in radians, as written in the QASM source.
+    params: Vec<f64>,
+}
+
+/// An OpenQASM 2.0 circuit parsed into an ordered command list plus its
+/// register declarations.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ParsedCircuit {
+    /// Quantum registers as `(name, size)` in declaration order.
+    qregs: Vec<(String, usize)>,
+    /// Classical registers as `(name, size)` in declaration order.
+    cregs: Vec<(String, usize)>,
+    commands: Vec<Command>,
+}
+
+/// Maps between a QASM gate name and its tket1 op `type`, recording whether the
+/// gate carries a rotation parameter.
+struct GateDef {
+    qasm: &'static str,
+    tket: &'static str,
+    parametric: bool,
+}
+
+const GATE_TABLE: &[GateDef] = &[
+    GateDef { qasm: "cx", tket: "CX", parametric: false },
+    GateDef { qasm: "h", tket: "H", parametric: false },
+    GateDef { qasm: "t", tket: "T", parametric: false },
+    GateDef { qasm: "tdg", tket: "Tdg", parametric: false },
+    GateDef { qasm: "s", tket: "S", parametric: false },
+    GateDef { qasm: "sdg", tket: "Sdg", parametric: false },
+    GateDef { qasm: "x", tket: "X", parametric: false },
+    GateDef { qasm: "y", tket: "Y", parametric: false },
+    GateDef { qasm: "z", tket: "Z", parametric: false },
+    GateDef { qasm: "rz", tket: "Rz", parametric: true },
+    GateDef { qasm: "rx", tket: "Rx", parametric: true },
+    GateDef { qasm: "ry", tket: "Ry", parametric: true },
+];
+
+fn gate_by_qasm(name: &str) -> Option<&'static GateDef> {
+    GATE_TABLE.iter().find(|g| g.qasm == name)
+}
+
+fn gate_by_tket(ty: &str) -> Option<&'static GateDef> {
+    GATE_TABLE.iter().find(|g| g.tket == ty)
+}
+
+/// Parses an OpenQASM 2.0 source string into a [`ParsedCircuit`].
+pub(crate) fn parse_qasm(qasm: &str) -> Result<ParsedCircuit> {
+    let mut circuit = ParsedCircuit::default();
+    for raw in qasm.split(';') {
+        // Drop line comments and surrounding whitespace.
+        let stmt: String = raw
+            .lines()
+            .map(|l| l.split("//").next().unwrap_or("").trim())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let stmt = stmt.trim();
+        if stmt.is_empty() {
+            continue;
+        }
+        if stmt.starts_with("OPENQASM") || stmt.starts_with("include") {
+            continue;
+        }
+        if let Some(rest) = stmt.strip_prefix("qreg ") {
+            let (name, size) = parse_register(rest)?;
+            circuit.qregs.push((name, size));
+        } else if let Some(rest) = stmt.strip_prefix("creg ") {
+            let (name, size) = parse_register(rest)?;
+            circuit.cregs.push((name, size));
+        } else {
+            circuit.commands.push(parse_gate(stmt)?);
+        }
+    }
+    Ok(circuit)
+}
+
+/// Parses a `name[size]` register declaration.
+fn parse_register(decl: &str) -> Result<(String, usize)> {
+    let open = decl
+        .find('[')
+        .ok_or_else(|| NativeConvertError::new(format!("malformed register `{decl}`")))?;
+    let close = decl
+        .find(']')
+        .ok_or_else(|| NativeConvertError::new(format!("malformed register `{decl}`")))?;
+    let name = decl[..open].trim().to_string();
+    let size = decl[open + 1..close]
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| NativeConvertError::new(format!("malformed register size in `{decl}`")))?;
+    Ok((name, size))
+}
+
+/// Parses a single gate application, e.g. `rz(0.5) q[0]` or `cx q[0],q[1]`.
+fn parse_gate(stmt: &str) -> Result<Command> {
+    let (head, args_str) = match stmt.find(|c: char| c.is_whitespace() || c == '(') {
+        Some(idx) => stmt.split_at(idx),
+        None => (stmt, ""),
+    };
+    let name = head.trim().to_ascii_lowercase();
+    let rest = args_str.trim();
+
+    // Optional parameter list in parentheses.
+    let (params, args) = if let Some(rest) = rest.strip_prefix('(') {
+        let close = rest
+            .find(')')
+            .ok_or_else(|| NativeConvertError::new(format!("unterminated params in `{stmt}`")))?;
+        let params = rest[..close]
+            .split(',')
+            .filter(|p| !p.trim().is_empty())
+            .map(parse_angle)
+            .collect::<Result<Vec<_>>>()?;
+        (params, rest[close + 1..].trim())
+    } else {
+        (Vec::new(), rest)
+    };
+
+    let is_measure = name == "measure";
+    if !is_measure && gate_by_qasm(&name).is_none() {
+        return Err(NativeConvertError::new(format!("unknown gate `{name}`")));
+    }
+
+    let mut qubits = Vec::new();
+    let mut clbits = Vec::new();
+    // `measure q[i] -> c[j]` lands the classical target after the arrow.
+    for (slot, chunk) in args.split("->").enumerate() {
+        for arg in chunk.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            let reg = parse_arg(arg)?;
+            if is_measure && slot == 1 {
+                clbits.push(reg);
+            } else {
+                qubits.push(reg);
+            }
+        }
+    }
+
+    Ok(Command {
+        name,
+        qubits,
+        clbits,
+        params,
+    })
+}
+
+/// Parses a `name[index]` bit reference.
+fn parse_arg(arg: &str) -> Result<(String, usize)> {
+    let open = arg
+        .find('[')
+        .ok_or_else(|| NativeConvertError::new(format!("malformed arg `{arg}`")))?;
+    let close = arg
+        .find(']')
+        .ok_or_else(|| NativeConvertError::new(format!("malformed arg `{arg}`")))?;
+    let name = arg[..open].trim().to_string();
+    let idx = arg[open + 1..close]
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| NativeConvertError::new(format!("malformed index in `{arg}`")))?;
+    Ok((name, idx))
+}
+
+/// Parses an angle expression, supporting plain radians and simple multiples of
+/// `pi` such as `pi`, `pi/2`, `2*pi`.
+fn parse_angle(expr: &str) -> Result<f64> {
+    let expr = expr.trim();
+    if let Ok(v) = expr.parse::<f64>() {
+        return Ok(v);
+    }
+    let err = || NativeConvertError::new(format!("cannot parse angle `{expr}`"));
+    if let Some((lhs, rhs)) = expr.split_once('/') {
+        return Ok(parse_angle(lhs)? / rhs.trim().parse::<f64>().map_err(|_| err())?);
+    }
+    if let Some((lhs, rhs)) = expr.split_once('*') {
+        return Ok(parse_angle(lhs)? * parse_angle(rhs)?);
+    }
+    match expr {
+        "pi" => Ok(PI),
+        "-pi" => Ok(-PI),
+        _ => Err(err()),
+    }
+}
+
+/// Converts a QASM string to a tket1 JSON string.
+pub(crate) fn qasm_to_json(qasm: &str) -> Result<String> {
+    let circuit = parse_qasm(qasm)?;
+    let value = circuit_to_json(&circuit)?;
+    serde_json::to_string(&value)
+        .map_err(|e| NativeConvertError::new(format!("failed to serialize JSON: {e}")))
+}
+
+/// Builds the tket1 dict for a parsed circuit.
+fn circuit_to_json(circuit: &ParsedCircuit) -> Result<Value> {
+    let qubits: Vec<Value> = circuit
+        .qregs
+        .iter()
+        .flat_map(|(name, size)| (0..*size).map(move |i| json!([name, [i]])))
+        .collect();
+    let bits: Vec<Value> = circuit
+        .cregs
+        .iter()
+        .flat_map(|(name, size)| (0..*size).map(move |i| json!([name, [i]])))
+        .collect();
+
+    let commands = circuit
+        .commands
+        .iter()
+        .map(command_to_json)
+        .collect::<Result<Vec<_>>>()?;
+
+    // tket1 records the qubit permutation applied implicitly by the circuit; a
+    // gate-only circuit leaves every qubit in place.
+    let implicit_permutation: Vec<Value> = qubits.iter().map(|q| json!([q, q])).collect();
+
+    Ok(json!({
+        "phase": "0.0",
+        "qubits": qubits,
+        "bits": bits,
+        "commands": commands,
+        "implicit_permutation": implicit_permutation,
+    }))
+}
+
+/// Converts half-turns (tket's angle unit) from/to the radians used in QASM.
+fn radians_to_half_turns(theta: f64) -> f64 {
+    theta / PI
+}
+
+fn half_turns_to_radians(turns: f64) -> f64 {
+    turns * PI
+}
+
+fn command_to_json(cmd: &Command) -> Result<Value> {
+    let ty = if cmd.name == "measure" {
+        "Measure"
+    } else {
+        gate_by_qasm(&cmd.name)
+            .ok_or_else(|| NativeConvertError::new(format!("unknown gate `{}`", cmd.name)))?
+            .tket
+    };
+
+    let mut op = json!({ "type": ty });
+    if !cmd.params.is_empty() {
+        let params: Vec<String> = cmd
+            .params
+            .iter()
+            .map(|&p| radians_to_half_turns(p).to_string())
+            .collect();
+        op["params"] = json!(params);
+    }
+
+    let args: Vec<Value> = cmd
+        .qubits
+        .iter()
+        .chain(cmd.clbits.iter())
+        .map(|(name, idx)| json!([name, [idx]]))
+        .collect();
+
+    Ok(json!({ "op": op, "args": args }))
+}
+
+/// Converts a tket1 JSON string back to OpenQASM 2.0.
+pub(crate) fn json_to_qasm(json: &str) -> Result<String> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|e| NativeConvertError::new(format!("invalid JSON: {e}")))?;
+
+    // Collect register sizes from the qubit/bit lists.
+    let qregs = register_sizes(&value, "qubits")?;
+    let cregs = register_sizes(&value, "bits")?;
+
+    let mut out = String::from("OPENQASM 2.0;\ninclude \"qelib1.inc\";\n");
+    for (name, size) in &qregs {
+        out.push_str(&format!("qreg {name}[{size}];\n"));
+    }
+    for (name, size) in &cregs {
+        out.push_str(&format!("creg {name}[{size}];\n"));
+    }
+
+    let commands = value["commands"]
+        .as_array()
+        .ok_or_else(|| NativeConvertError::new("missing `commands` array"))?;
+    for command in commands {
+        out.push_str(&command_to_qasm(command)?);
+    }
+    Ok(out)
+}
+
+/// Determines the declared size of each register from a `qubits`/`bits` list.
+fn register_sizes(value: &Value, key: &str) -> Result<Vec<(String, usize)>> {
+    let mut sizes: BTreeMap<String, usize> = BTreeMap::new();
+    let Some(entries) = value.get(key).and_then(Value::as_array) else {
+        return Ok(Vec::new());
+    };
+    for entry in entries {
+        let (name, idx) = parse_json_register(entry)?;
+        let slot = sizes.entry(name).or_insert(0);
+        *slot = (*slot).max(idx + 1);
+    }
+    Ok(sizes.into_iter().collect())
+}
+
+/// Parses a `["q", [i]]` register reference from JSON.
+fn parse_json_register(value: &Value) -> Result<(String, usize)> {
+    let arr = value
+        .as_array()
+        .ok_or_else(|| NativeConvertError::new("malformed register entry"))?;
+    let name = arr
+        .first()
+        .and_then(Value::as_str)
+        .ok_or_else(|| NativeConvertError::new("malformed register name"))?
+        .to_string();
+    let idx = arr
+        .get(1)
+        .and_then(Value::as_array)
+        .and_then(|a| a.first())
+        .and_then(Value::as_u64)
+        .ok_or_else(|| NativeConvertError::new("malformed register index"))?;
+    Ok((name, idx as usize))
+}
+
+fn command_to_qasm(command: &Value) -> Result<String> {
+    let op = &command["op"];
+    let ty = op["type"]
+        .as_str()
+        .ok_or_else(|| NativeConvertError::new("missing op type"))?;
+
+    let args = command["args"]
+        .as_array()
+        .ok_or_else(|| NativeConvertError::new("missing command args"))?;
+
+    if ty == "Measure" {
+        if args.len() != 2 {
+            return Err(NativeConvertError::new("measure expects two args"));
+        }
+        let (qn, qi) = parse_json_register(&args[0])?;
+        let (cn, ci) = parse_json_register(&args[1])?;
+        return Ok(format!("measure {qn}[{qi}] -> {cn}[{ci}];\n"));
+    }
+
+    let def = gate_by_tket(ty)
+        .ok_or_else(|| NativeConvertError::new(format!("unknown op type `{ty}`")))?;
+
+    let mut line = def.qasm.to_string();
+    if def.parametric {
+        let params = op["params"]
+            .as_array()
+            .ok_or_else(|| NativeConvertError::new(format!("`{ty}` missing params")))?;
+        let rendered: Vec<String> = params
+            .iter()
+            .map(|p| parse_half_turns(p).map(|t| half_turns_to_radians(t).to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        line.push_str(&format!("({})", rendered.join(",")));
+    }
+
+    let rendered_args: Vec<String> = args
+        .iter()
+        .map(|a| parse_json_register(a).map(|(name, idx)| format!("{name}[{idx}]")))
+        .collect::<Result<Vec<_>>>()?;
+    line.push(' ');
+    line.push_str(&rendered_args.join(","));
+    line.push_str(";\n");
+    Ok(line)
+}
+
+/// Reads a tket1 param, which may be encoded as a JSON number or a string.
+fn parse_half_turns(value: &Value) -> Result<f64> {
+    match value {
+        Value::Number(n) => n
+            .as_f64()
+            .ok_or_else(|| NativeConvertError::new("non-float param")),
+        Value::String(s) => s
+            .parse::<f64>()
+            .map_err(|_| NativeConvertError::new(format!("cannot parse param `{s}`"))),
+        _ => Err(NativeConvertError::new("malformed param")),
+    }
+}